@@ -19,15 +19,122 @@ pub trait Object: Sized {
     fn encode(&self) -> Vec<u8>;
 
     fn decode(_: &[u8]) -> Result<Self, VerifyError>;
+
+    /// Default `decode` implementation for `rlp`-derived objects: decodes via
+    /// `rlp` and, on failure, tags the resulting `DecoderError` with
+    /// `type_name` so callers can tell which object failed and why (the
+    /// `rlp::DecoderError` kind), instead of getting a bare
+    /// `VerifyError::SerdeError`. `rlp` does not report which field within
+    /// the object failed.
+    fn decode_with_context(type_name: &'static str, data: &[u8]) -> Result<Self, VerifyError>
+    where
+        Self: Decodable,
+    {
+        rlp::decode(data).map_err(|e| VerifyError::SerdeError(DecodeError::new(type_name, e)))
+    }
+
+    /// Strict counterpart to `decode`/`decode_with_context` for data coming
+    /// from an untrusted counterparty chain, where lenient RLP decoding is
+    /// itself an attack surface: two different byte strings must not be
+    /// allowed to decode to the same object, or commitment proofs over the
+    /// raw bytes stop meaning anything. After the normal decode, this
+    /// re-encodes the result and rejects it unless the bytes round-trip
+    /// exactly, which catches trailing bytes and non-minimal integers in one
+    /// check; over-long `State`/`Ordering` lists never get that far, as their
+    /// own decoders already reject them. Use `decode` instead
+    /// for data produced locally, where the extra re-encode is wasted work.
+    fn decode_untrusted(type_name: &'static str, data: &[u8]) -> Result<Self, VerifyError>
+    where
+        Self: Decodable,
+    {
+        let value = Self::decode_with_context(type_name, data)?;
+        if value.encode() != data {
+            return Err(VerifyError::SerdeError(DecodeError {
+                type_name,
+                kind: DecodeErrorKind::NonCanonicalEncoding,
+            }));
+        }
+        Ok(value)
+    }
+}
+
+/// The reason `rlp` gave up while decoding an `Object`, tagged with the name
+/// of the object being decoded, so relayers get more than a single opaque
+/// error code. This identifies which *object* failed to decode and why (the
+/// `rlp::DecoderError` kind); it does not identify which field within the
+/// object, since `rlp` itself does not surface that.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    pub type_name: &'static str,
+    pub kind: DecodeErrorKind,
+}
+
+impl DecodeError {
+    pub fn new(type_name: &'static str, err: rlp::DecoderError) -> Self {
+        Self {
+            type_name,
+            kind: err.into(),
+        }
+    }
+}
+
+/// Mirrors `rlp::DecoderError`'s variants so they can be carried inside
+/// `VerifyError` without exposing `rlp`'s own error type in the public
+/// error surface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeErrorKind {
+    RlpIsTooShort,
+    RlpIsTooBig,
+    RlpInvalidIndirection,
+    RlpExpectedToBeList,
+    RlpExpectedToBeData,
+    RlpIncorrectListLen,
+    RlpDataLenWithZeroPrefix,
+    RlpListLenWithZeroPrefix,
+    RlpInconsistentLengthAndData,
+    Custom(&'static str),
+    Other,
+
+    /// Decoding succeeded, but re-encoding the decoded value did not
+    /// reproduce the original bytes. Raised only by
+    /// `Object::decode_untrusted`, which rejects non-canonical input:
+    /// trailing bytes after the top-level item and non-minimal integers
+    /// fail to round-trip. `State`/`Ordering` lists longer than one element
+    /// are rejected by those types' decoders instead, with the `rlp` error
+    /// kind, not this one.
+    NonCanonicalEncoding,
+}
+
+impl From<rlp::DecoderError> for DecodeErrorKind {
+    fn from(err: rlp::DecoderError) -> Self {
+        match err {
+            rlp::DecoderError::RlpIsTooShort => DecodeErrorKind::RlpIsTooShort,
+            rlp::DecoderError::RlpIsTooBig => DecodeErrorKind::RlpIsTooBig,
+            rlp::DecoderError::RlpInvalidIndirection => DecodeErrorKind::RlpInvalidIndirection,
+            rlp::DecoderError::RlpExpectedToBeList => DecodeErrorKind::RlpExpectedToBeList,
+            rlp::DecoderError::RlpExpectedToBeData => DecodeErrorKind::RlpExpectedToBeData,
+            rlp::DecoderError::RlpIncorrectListLen => DecodeErrorKind::RlpIncorrectListLen,
+            rlp::DecoderError::RlpDataLenWithZeroPrefix => {
+                DecodeErrorKind::RlpDataLenWithZeroPrefix
+            }
+            rlp::DecoderError::RlpListLenWithZeroPrefix => {
+                DecodeErrorKind::RlpListLenWithZeroPrefix
+            }
+            rlp::DecoderError::RlpInconsistentLengthAndData => {
+                DecodeErrorKind::RlpInconsistentLengthAndData
+            }
+            rlp::DecoderError::Custom(s) => DecodeErrorKind::Custom(s),
+            _ => DecodeErrorKind::Other,
+        }
+    }
 }
 
 #[derive(Debug)]
-#[repr(i8)]
 pub enum VerifyError {
-    FoundNoMessage = 100,
+    FoundNoMessage,
     EventNotMatch,
     InvalidReceiptProof,
-    SerdeError,
+    SerdeError(DecodeError),
 
     WrongClient,
     WrongConnectionId,
@@ -58,7 +165,42 @@ pub enum VerifyError {
 
 impl From<VerifyError> for i8 {
     fn from(value: VerifyError) -> Self {
-        value as i8
+        // Discriminants are assigned by hand (rather than `value as i8`)
+        // because `SerdeError` now carries a `DecodeError` payload, so the
+        // enum can no longer use a fieldless `repr(i8)` layout. The values
+        // below match the previous auto-incremented `repr(i8)` discriminants.
+        match value {
+            VerifyError::FoundNoMessage => 100,
+            VerifyError::EventNotMatch => 101,
+            VerifyError::InvalidReceiptProof => 102,
+            VerifyError::SerdeError(_) => 103,
+
+            VerifyError::WrongClient => 104,
+            VerifyError::WrongConnectionId => 105,
+            VerifyError::WrongConnectionnNumber => 106,
+            VerifyError::WrongPortId => 107,
+            VerifyError::WrongCommonHexId => 108,
+
+            VerifyError::ConnectionsWrong => 109,
+
+            VerifyError::WrongConnectionCnt => 110,
+            VerifyError::WrongConnectionState => 111,
+            VerifyError::WrongConnectionCounterparty => 112,
+            VerifyError::WrongConnectionClient => 113,
+            VerifyError::WrongConnectionNextChannelNumber => 114,
+            VerifyError::WrongConnectionArgs => 115,
+
+            VerifyError::WrongChannelState => 116,
+            VerifyError::WrongChannel => 117,
+            VerifyError::WrongChannelArgs => 118,
+            VerifyError::WrongChannelSequence => 119,
+
+            VerifyError::WrongUnusedPacket => 120,
+            VerifyError::WrongPacketSequence => 121,
+            VerifyError::WrongPacketStatus => 122,
+            VerifyError::WrongPacketContent => 123,
+            VerifyError::WrongPacketArgs => 124,
+        }
     }
 }
 
@@ -77,14 +219,28 @@ pub enum State {
 impl Encodable for State {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
         let state = *self as u8;
-        s.begin_list(1);
         s.append(&state);
     }
 }
 
 impl Decodable for State {
     fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
-        let state: u8 = rlp.val_at(0)?;
+        // Canonical encoding is a bare scalar byte. The `legacy-list-state-encoding`
+        // feature additionally accepts the old one-element-list shape during the
+        // migration window; a list of any other length is always rejected so that
+        // a `State` never has more than one valid encoding for a given value.
+        let state: u8 = if rlp.is_list() {
+            if cfg!(feature = "legacy-list-state-encoding") {
+                if rlp.item_count()? != 1 {
+                    return Err(rlp::DecoderError::RlpIncorrectListLen);
+                }
+                rlp.val_at(0)?
+            } else {
+                return Err(rlp::DecoderError::RlpExpectedToBeData);
+            }
+        } else {
+            rlp.as_val()?
+        };
         match state {
             1 => Ok(State::Unknown),
             2 => Ok(State::Init),
@@ -109,14 +265,27 @@ pub enum Ordering {
 impl Encodable for Ordering {
     fn rlp_append(&self, s: &mut rlp::RlpStream) {
         let ordering = *self as u8;
-        s.begin_list(1);
         s.append(&ordering);
     }
 }
 
 impl Decodable for Ordering {
     fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
-        let ordering: u8 = rlp.val_at(0)?;
+        // See `Decodable for State` above: scalar is canonical, the legacy
+        // one-element-list shape is accepted only under the
+        // `legacy-list-state-encoding` feature, and longer lists are always rejected.
+        let ordering: u8 = if rlp.is_list() {
+            if cfg!(feature = "legacy-list-state-encoding") {
+                if rlp.item_count()? != 1 {
+                    return Err(rlp::DecoderError::RlpIncorrectListLen);
+                }
+                rlp.val_at(0)?
+            } else {
+                return Err(rlp::DecoderError::RlpExpectedToBeData);
+            }
+        } else {
+            rlp.as_val()?
+        };
         match ordering {
             1 => Ok(Ordering::Unknown),
             2 => Ok(Ordering::Unordered),
@@ -189,7 +358,7 @@ impl Object for Packet {
     }
 
     fn decode(data: &[u8]) -> Result<Self, VerifyError> {
-        rlp::decode(data).map_err(|_| VerifyError::SerdeError)
+        Self::decode_with_context("Packet", data)
     }
 }
 
@@ -215,6 +384,61 @@ impl Packet {
     }
 }
 
+/// Borrowed companion to `Packet`: `data` is a slice into the buffer passed
+/// to `decode_borrowed` rather than an owned, heap-allocated copy. Use this
+/// in the verifier's hot path, where cycles are the budget and the payload
+/// is only compared or hashed, not kept around; use `Packet` on the
+/// construction side, where an owned value is needed anyway.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PacketRef<'a> {
+    pub sequence: u16,
+    pub source_port_id: String,
+    pub source_channel_id: String,
+    pub destination_port_id: String,
+    pub destination_channel_id: String,
+    pub data: &'a [u8],
+    pub timeout_height: u64,
+    pub timeout_timestamp: u64,
+}
+
+impl<'a> PacketRef<'a> {
+    pub fn decode_borrowed(data: &'a [u8]) -> Result<Self, VerifyError> {
+        let rlp = rlp::Rlp::new(data);
+        let map_err =
+            |e: rlp::DecoderError| VerifyError::SerdeError(DecodeError::new("PacketRef", e));
+        Ok(Self {
+            sequence: rlp.val_at(0).map_err(map_err)?,
+            source_port_id: rlp.val_at(1).map_err(map_err)?,
+            source_channel_id: rlp.val_at(2).map_err(map_err)?,
+            destination_port_id: rlp.val_at(3).map_err(map_err)?,
+            destination_channel_id: rlp.val_at(4).map_err(map_err)?,
+            data: rlp.at(5).map_err(map_err)?.data().map_err(map_err)?,
+            timeout_height: rlp.val_at(6).map_err(map_err)?,
+            timeout_timestamp: rlp.val_at(7).map_err(map_err)?,
+        })
+    }
+
+    pub fn equal_unless_sequence(&self, other: &Self) -> bool {
+        (
+            &self.source_port_id,
+            &self.source_channel_id,
+            &self.destination_port_id,
+            &self.destination_channel_id,
+            self.data,
+            self.timeout_height,
+            self.timeout_timestamp,
+        ) == (
+            &other.source_port_id,
+            &other.source_channel_id,
+            &other.destination_port_id,
+            &other.destination_channel_id,
+            other.data,
+            other.timeout_height,
+            other.timeout_timestamp,
+        )
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, RlpEncodable, RlpDecodable)]
 pub struct Version {
     pub identifier: String,
@@ -257,7 +481,7 @@ impl Object for ConnectionEnd {
     }
 
     fn decode(data: &[u8]) -> Result<Self, VerifyError> {
-        rlp::decode(data).map_err(|_| VerifyError::SerdeError)
+        Self::decode_with_context("ConnectionEnd", data)
     }
 }
 
@@ -276,7 +500,7 @@ impl Object for ChannelEnd {
     }
 
     fn decode(data: &[u8]) -> Result<Self, VerifyError> {
-        rlp::decode(data).map_err(|_| VerifyError::SerdeError)
+        Self::decode_with_context("ChannelEnd", data)
     }
 }
 
@@ -293,13 +517,85 @@ impl Object for PacketAck {
     }
 
     fn decode(data: &[u8]) -> Result<Self, VerifyError> {
-        rlp::decode(data).map_err(|_| VerifyError::SerdeError)
+        Self::decode_with_context("PacketAck", data)
     }
 }
 
+/// Borrowed companion to `PacketAck`, mirroring `PacketRef`: `ack` borrows
+/// from the decoded buffer and the nested packet is itself a `PacketRef`, so
+/// decoding a `PacketAck` for verification no longer copies either payload.
+pub struct PacketAckRef<'a> {
+    pub ack: &'a [u8],
+    pub packet: PacketRef<'a>,
+}
+
+impl<'a> PacketAckRef<'a> {
+    pub fn decode_borrowed(data: &'a [u8]) -> Result<Self, VerifyError> {
+        let rlp = rlp::Rlp::new(data);
+        let map_err =
+            |e: rlp::DecoderError| VerifyError::SerdeError(DecodeError::new("PacketAckRef", e));
+        let ack = rlp.at(0).map_err(map_err)?.data().map_err(map_err)?;
+        let packet = PacketRef::decode_borrowed(rlp.at(1).map_err(map_err)?.as_raw())?;
+        Ok(Self { ack, packet })
+    }
+}
+
+/// Declares a tagged envelope enum over a set of `Object` types, each with a
+/// one-byte discriminant: an `Envelope` sum type, an `encode` that prepends
+/// the tag, and a `from_tagged` dispatcher that reads the tag back off and
+/// routes to the matching `Object::decode`. This lets message queues and the
+/// cell-data verifier store heterogeneous IBC objects in one column and
+/// recover their type without out-of-band knowledge.
+macro_rules! ibc_messages {
+    ($($variant:ident => $tag:literal),+ $(,)?) => {
+        pub enum Envelope {
+            $($variant($variant),)+
+        }
+
+        impl Envelope {
+            pub fn encode(&self) -> Vec<u8> {
+                let (tag, mut body): (u8, Vec<u8>) = match self {
+                    $(Envelope::$variant(inner) => ($tag, Object::encode(inner)),)+
+                };
+                body.insert(0, tag);
+                body
+            }
+
+            pub fn from_tagged(data: &[u8]) -> Result<Self, VerifyError> {
+                let (tag, rest) = data.split_first().ok_or_else(|| {
+                    VerifyError::SerdeError(DecodeError {
+                        type_name: "Envelope",
+                        kind: DecodeErrorKind::Custom("missing type tag"),
+                    })
+                })?;
+                match *tag {
+                    $($tag => Ok(Envelope::$variant(<$variant as Object>::decode(rest)?)),)+
+                    _ => Err(VerifyError::SerdeError(DecodeError {
+                        type_name: "Envelope",
+                        kind: DecodeErrorKind::Custom("unknown envelope tag"),
+                    })),
+                }
+            }
+        }
+    };
+}
+
+ibc_messages! {
+    Packet => 1,
+    ConnectionEnd => 2,
+    ChannelEnd => 3,
+    PacketAck => 4,
+}
+
 #[cfg(test)]
 mod tests {
+    use super::Envelope;
+    use super::Object;
     use super::Ordering;
+    use super::Packet;
+    use super::PacketAck;
+    use super::PacketAckRef;
+    use super::PacketRef;
     use super::State;
     use super::Vec;
 
@@ -336,4 +632,112 @@ mod tests {
             assert_eq!(actual, orderings[i - 1]);
         }
     }
+
+    #[test]
+    fn non_canonical_state_list_is_rejected() {
+        // Canonical `State` is a bare scalar (or, under the
+        // `legacy-list-state-encoding` feature, a one-element list); a
+        // longer list is never valid, so it must be rejected outright rather
+        // than silently truncated to its first element.
+        let mut stream = rlp::RlpStream::new_list(2);
+        stream.append(&1u8);
+        stream.append(&0u8);
+        let bytes = stream.out().to_vec();
+
+        assert!(rlp::decode::<State>(&bytes).is_err());
+    }
+
+    #[test]
+    fn encode_state_uses_scalar_form() {
+        let bytes = rlp::encode(&State::Open).to_vec();
+        assert_eq!(bytes, rlp::encode(&(State::Open as u8)).to_vec());
+    }
+
+    #[cfg(feature = "legacy-list-state-encoding")]
+    #[test]
+    fn decode_state_accepts_legacy_single_element_list() {
+        let mut stream = rlp::RlpStream::new_list(1);
+        stream.append(&(State::Open as u8));
+        let bytes = stream.out().to_vec();
+
+        assert_eq!(rlp::decode::<State>(&bytes).unwrap(), State::Open);
+    }
+
+    #[cfg(not(feature = "legacy-list-state-encoding"))]
+    #[test]
+    fn decode_state_rejects_legacy_list_without_feature() {
+        let mut stream = rlp::RlpStream::new_list(1);
+        stream.append(&(State::Open as u8));
+        let bytes = stream.out().to_vec();
+
+        assert!(rlp::decode::<State>(&bytes).is_err());
+    }
+
+    #[test]
+    fn decode_untrusted_accepts_canonical_packet() {
+        let packet = Packet::default();
+        let bytes = packet.encode();
+
+        let decoded = Packet::decode_untrusted("Packet", &bytes).unwrap();
+        assert!(decoded.equal_unless_sequence(&packet));
+    }
+
+    #[test]
+    fn decode_untrusted_rejects_trailing_bytes() {
+        let packet = Packet::default();
+        let mut bytes = packet.encode();
+        bytes.push(0);
+
+        assert!(Packet::decode_untrusted("Packet", &bytes).is_err());
+    }
+
+    #[test]
+    fn envelope_round_trips_by_tag() {
+        let packet = Packet::default();
+        let tagged = Envelope::Packet(packet.clone()).encode();
+        assert_eq!(tagged[0], 1);
+
+        match Envelope::from_tagged(&tagged).unwrap() {
+            Envelope::Packet(decoded) => assert!(decoded.equal_unless_sequence(&packet)),
+            _ => panic!("expected Envelope::Packet"),
+        }
+    }
+
+    #[test]
+    fn envelope_from_tagged_rejects_unknown_tag() {
+        let tagged = Envelope::Packet(Packet::default()).encode();
+        let mut bad_tag = tagged;
+        bad_tag[0] = 0xff;
+
+        assert!(Envelope::from_tagged(&bad_tag).is_err());
+    }
+
+    #[test]
+    fn packet_ref_decodes_without_copying_data() {
+        let mut packet = Packet::default();
+        packet.data = vec![1, 2, 3, 4];
+        let bytes = packet.encode();
+
+        let decoded = PacketRef::decode_borrowed(&bytes).unwrap();
+        assert_eq!(decoded.sequence, packet.sequence);
+        assert_eq!(decoded.data, packet.data.as_slice());
+        // `data` borrows straight from `bytes`, it isn't a fresh allocation.
+        assert!(bytes.as_ptr_range().contains(&decoded.data.as_ptr()));
+        assert!(decoded.equal_unless_sequence(&PacketRef::decode_borrowed(&bytes).unwrap()));
+    }
+
+    #[test]
+    fn packet_ack_ref_decodes_without_copying_payloads() {
+        let mut packet = Packet::default();
+        packet.data = vec![9, 9, 9];
+        let ack = PacketAck {
+            ack: vec![5, 6, 7],
+            packet,
+        };
+        let bytes = ack.encode();
+
+        let decoded = PacketAckRef::decode_borrowed(&bytes).unwrap();
+        assert_eq!(decoded.ack, ack.ack.as_slice());
+        assert_eq!(decoded.packet.data, ack.packet.data.as_slice());
+    }
 }